@@ -0,0 +1,158 @@
+//! Rotation-aware geometry for keys: computing their absolute corners in
+//! board space and a layout-wide bounding box, instead of treating `r`/`rx`/
+//! `ry` as inert fields.
+
+use crate::{Key, Keyboard};
+
+/// A point in board space, measured in KLE units.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Point {
+    pub x: f64,
+    pub y: f64,
+}
+
+/// The axis-aligned extents of one or more keys, in KLE units.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct BoundingBox {
+    pub min_x: f64,
+    pub min_y: f64,
+    pub max_x: f64,
+    pub max_y: f64,
+}
+
+impl BoundingBox {
+    pub fn width(&self) -> f64 {
+        self.max_x - self.min_x
+    }
+
+    pub fn height(&self) -> f64 {
+        self.max_y - self.min_y
+    }
+}
+
+impl Key {
+    /// Returns this key's four corners in board space, applying the affine
+    /// rotation (`r` degrees about `(rx, ry)`) that KLE uses for rotated
+    /// clusters. Corners are returned in order: top-left, top-right,
+    /// bottom-right, bottom-left.
+    pub fn corners(&self) -> [Point; 4] {
+        let w = self.properties.w.unwrap_or(1.0);
+        let h = self.properties.h.unwrap_or(1.0);
+
+        let local = [
+            Point {
+                x: self.x,
+                y: self.y,
+            },
+            Point {
+                x: self.x + w,
+                y: self.y,
+            },
+            Point {
+                x: self.x + w,
+                y: self.y + h,
+            },
+            Point {
+                x: self.x,
+                y: self.y + h,
+            },
+        ];
+
+        let angle = self.properties.r.unwrap_or(0.0);
+        if angle == 0.0 {
+            return local;
+        }
+
+        let rx = self.properties.rx.unwrap_or(0.0);
+        let ry = self.properties.ry.unwrap_or(0.0);
+        let (sin, cos) = angle.to_radians().sin_cos();
+
+        local.map(|p| rotate_point(p, rx, ry, sin, cos))
+    }
+
+    /// Returns the axis-aligned bounding box of this key's rotated corners.
+    pub fn bounding_box(&self) -> BoundingBox {
+        bounding_box_of(&self.corners())
+    }
+
+    /// Returns whether `point` (in board space) falls within this key,
+    /// accounting for rotation.
+    pub fn contains(&self, point: Point) -> bool {
+        let w = self.properties.w.unwrap_or(1.0);
+        let h = self.properties.h.unwrap_or(1.0);
+        let angle = self.properties.r.unwrap_or(0.0);
+
+        // Rotate the point backwards into the key's local, un-rotated frame
+        // instead of rotating the rectangle forwards, so the hit test stays
+        // a simple axis-aligned comparison.
+        let local_point = if angle == 0.0 {
+            point
+        } else {
+            let rx = self.properties.rx.unwrap_or(0.0);
+            let ry = self.properties.ry.unwrap_or(0.0);
+            let (sin, cos) = (-angle).to_radians().sin_cos();
+            rotate_point(point, rx, ry, sin, cos)
+        };
+
+        local_point.x >= self.x
+            && local_point.x <= self.x + w
+            && local_point.y >= self.y
+            && local_point.y <= self.y + h
+    }
+
+    /// Returns whether this key's rotated bounding box overlaps `other`'s.
+    ///
+    /// This checks axis-aligned bounding boxes rather than the true rotated
+    /// rectangles, so it can report an overlap for two rotated keys whose
+    /// actual outlines don't intersect; it's meant for coarse collision
+    /// detection, not exact geometry.
+    pub fn overlaps(&self, other: &Key) -> bool {
+        let a = self.bounding_box();
+        let b = other.bounding_box();
+        a.min_x < b.max_x && a.max_x > b.min_x && a.min_y < b.max_y && a.max_y > b.min_y
+    }
+}
+
+impl Keyboard {
+    /// Returns the bounding box spanning every key's rotated corners, or
+    /// `None` if the layout has no keys.
+    pub fn bounding_box(&self) -> Option<BoundingBox> {
+        self.keys
+            .iter()
+            .map(|key| key.bounding_box())
+            .reduce(|a, b| BoundingBox {
+                min_x: a.min_x.min(b.min_x),
+                min_y: a.min_y.min(b.min_y),
+                max_x: a.max_x.max(b.max_x),
+                max_y: a.max_y.max(b.max_y),
+            })
+    }
+}
+
+fn rotate_point(p: Point, cx: f64, cy: f64, sin: f64, cos: f64) -> Point {
+    let dx = p.x - cx;
+    let dy = p.y - cy;
+    Point {
+        x: cx + dx * cos - dy * sin,
+        y: cy + dx * sin + dy * cos,
+    }
+}
+
+fn bounding_box_of(points: &[Point]) -> BoundingBox {
+    let min_x = points.iter().map(|p| p.x).fold(f64::INFINITY, f64::min);
+    let max_x = points
+        .iter()
+        .map(|p| p.x)
+        .fold(f64::NEG_INFINITY, f64::max);
+    let min_y = points.iter().map(|p| p.y).fold(f64::INFINITY, f64::min);
+    let max_y = points
+        .iter()
+        .map(|p| p.y)
+        .fold(f64::NEG_INFINITY, f64::max);
+    BoundingBox {
+        min_x,
+        min_y,
+        max_x,
+        max_y,
+    }
+}
@@ -1,8 +1,49 @@
 use serde::{Deserialize, Serialize};
 use serde_json::Value;
 use std::collections::HashMap;
+use std::fmt;
 
-#[derive(Debug, Serialize, Deserialize)]
+pub mod geometry;
+pub mod parser;
+pub mod search;
+pub mod svg;
+
+pub use parser::ParseError;
+
+/// An error returned by [`Keyboard::parse`]: either a syntax error from the
+/// KLE tokenizer/parser, or a schema error while decoding a property object
+/// or the metadata block into its Rust type.
+#[derive(Debug)]
+pub enum KleError {
+    Parse(ParseError),
+    Schema(serde_json::Error),
+}
+
+impl fmt::Display for KleError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            KleError::Parse(e) => write!(f, "{}", e),
+            KleError::Schema(e) => write!(f, "{}", e),
+        }
+    }
+}
+
+impl std::error::Error for KleError {}
+
+impl From<ParseError> for KleError {
+    fn from(e: ParseError) -> Self {
+        KleError::Parse(e)
+    }
+}
+
+impl From<serde_json::Error> for KleError {
+    fn from(e: serde_json::Error) -> Self {
+        KleError::Schema(e)
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, PartialEq)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
 pub struct KeyboardMetadata {
     pub author: Option<String>,
     pub backcolor: Option<String>,
@@ -15,13 +56,15 @@ pub struct KeyboardMetadata {
     pub switch_type: Option<String>,
 }
 
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Serialize, Deserialize, PartialEq)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
 pub struct Background {
     pub name: String,
     pub style: String,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize, Default)]
+#[derive(Debug, Clone, Serialize, Deserialize, Default, PartialEq)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
 pub struct KeyProperties {
     // Next key only properties
     pub x: Option<f64>,
@@ -45,13 +88,16 @@ pub struct KeyProperties {
     pub c: Option<String>, // keycap color
     pub t: Option<String>, // text color
     pub g: Option<bool>,   // ghosted
-    pub a: Option<u8>,     // text alignment
-    pub f: Option<u8>,     // primary font size
-    pub f2: Option<u8>,    // secondary font size
+    #[cfg_attr(feature = "schema", schemars(range(min = 0, max = 7)))]
+    pub a: Option<u8>, // text alignment
+    #[cfg_attr(feature = "schema", schemars(range(min = 1, max = 9)))]
+    pub f: Option<u8>, // primary font size
+    #[cfg_attr(feature = "schema", schemars(range(min = 1, max = 9)))]
+    pub f2: Option<u8>, // secondary font size
     pub p: Option<String>, // profile & row
 }
 
-#[derive(Debug)]
+#[derive(Debug, PartialEq)]
 pub struct Key {
     pub legends: Vec<String>,
     pub properties: KeyProperties,
@@ -59,101 +105,41 @@ pub struct Key {
     pub y: f64,
 }
 
-#[derive(Debug)]
+#[derive(Debug, PartialEq)]
 pub struct Keyboard {
     pub metadata: Option<KeyboardMetadata>,
     pub keys: Vec<Key>,
 }
 
 impl Keyboard {
-    fn preprocess_raw_data(raw_data: &str) -> String {
-        let mut processed = String::new();
-        let mut lines: Vec<&str> = raw_data
-            .split('\n')
-            .map(|line| line.trim())
-            .filter(|line| !line.is_empty())
-            .collect();
-
-        // Remove trailing commas from each line
-        (0..lines.len()).for_each(|i| {
-            if lines[i].ends_with(',') {
-                lines[i] = &lines[i][..lines[i].len() - 1];
-            }
-        });
-
-        // Join lines and wrap in array brackets
-        processed.push('[');
-        for (i, line) in lines.iter().enumerate() {
-            if i > 0 {
-                processed.push(',');
-            }
-            processed.push_str(line);
-        }
-        processed.push(']');
-
-        // Add quotes around property names in objects
-        let mut result = String::new();
-        let mut in_string = false;
-        let mut in_object = false;
-        let mut last_char: Option<char> = None;
-        let mut property_name = String::new();
-
-        processed.chars().for_each(|c| {
-            match c {
-                '"' => {
-                    in_string = !in_string;
-                    result.push(c);
-                }
-                '{' if !in_string => {
-                    in_object = true;
-                    result.push(c);
-                }
-                '}' if !in_string => {
-                    in_object = false;
-                    result.push(c);
-                }
-                ':' if in_object && !in_string => {
-                    if !property_name.is_empty() && !property_name.starts_with('"') {
-                        result.truncate(result.len() - property_name.len());
-                        result.push('"');
-                        result.push_str(&property_name);
-                        result.push('"');
-                    }
-                    result.push(c);
-                    property_name.clear();
-                }
-                ',' if in_object && !in_string => {
-                    result.push(c);
-                    property_name.clear();
-                }
-                _ => {
-                    if in_object
-                        && !in_string
-                        && last_char.map_or(true, |ch| ch == '{' || ch == ',')
-                    {
-                        property_name.clear();
-                    }
-                    if in_object && !in_string {
-                        property_name.push(c);
-                    }
-                    result.push(c);
-                }
-            }
-            last_char = Some(c);
-        });
-
-        result
+    /// Returns a JSON Schema describing the `KeyboardMetadata`, `Background`,
+    /// and `KeyProperties` types, keyed by name, so downstream tools can
+    /// validate layout files before calling [`Keyboard::parse`] or offer
+    /// editor completion against the legal property set.
+    #[cfg(feature = "schema")]
+    pub fn json_schema() -> serde_json::Value {
+        let mut schema = serde_json::Map::new();
+        schema.insert(
+            "keyboard_metadata".to_string(),
+            serde_json::to_value(schemars::schema_for!(KeyboardMetadata)).unwrap(),
+        );
+        schema.insert(
+            "background".to_string(),
+            serde_json::to_value(schemars::schema_for!(Background)).unwrap(),
+        );
+        schema.insert(
+            "key_properties".to_string(),
+            serde_json::to_value(schemars::schema_for!(KeyProperties)).unwrap(),
+        );
+        Value::Object(schema)
     }
 
-    pub fn parse(raw_data: &str) -> Result<Self, serde_json::Error> {
+    pub fn parse(raw_data: &str) -> Result<Self, KleError> {
         // Normalize line endings and clean up whitespace
         let raw_data = raw_data.replace("\r\n", "\n").replace('\r', "\n");
         let raw_data = raw_data.trim();
 
-        // Preprocess the data to ensure valid JSON
-        let processed_data = Self::preprocess_raw_data(raw_data);
-
-        let mut data: Vec<Value> = serde_json::from_str(&processed_data)?;
+        let mut data: Vec<Value> = parser::parse_document(raw_data)?;
 
         // Extract metadata if present
         let metadata = if !data.is_empty() && data[0].is_object() {
@@ -199,6 +185,18 @@ impl Keyboard {
                     if props.p.is_some() {
                         current_properties.p = props.p.clone();
                     }
+                    // Rotation applies to every key in the cluster until
+                    // it's explicitly changed, so it's persistent too
+                    // rather than a per-key property.
+                    if props.r.is_some() {
+                        current_properties.r = props.r;
+                    }
+                    if props.rx.is_some() {
+                        current_properties.rx = props.rx;
+                    }
+                    if props.ry.is_some() {
+                        current_properties.ry = props.ry;
+                    }
 
                     // Store single-key properties
                     current_properties.x = props.x;
@@ -212,9 +210,6 @@ impl Keyboard {
                     current_properties.l = props.l;
                     current_properties.n = props.n;
                     current_properties.d = props.d;
-                    current_properties.r = props.r;
-                    current_properties.rx = props.rx;
-                    current_properties.ry = props.ry;
                 } else if item.is_string() {
                     // Process key
                     let legends: Vec<String> = item
@@ -224,23 +219,15 @@ impl Keyboard {
                         .map(String::from)
                         .collect();
 
-                    // Apply position adjustments
+                    // Apply position adjustments. `x`/`y` are always in the
+                    // key's local, unrotated frame, whether or not a
+                    // rotation is active for this cluster; `rx`/`ry`/`r`
+                    // (carried on `properties`) tell the geometry subsystem
+                    // how to rotate that local position around the cluster's
+                    // origin.
                     let x = current_x + current_properties.x.unwrap_or(0.0);
                     let y = current_y + current_properties.y.unwrap_or(0.0);
 
-                    // If the key is rotated, use the absolute x and y values
-                    let (x, y) = if current_properties.r.is_some()
-                        || current_properties.rx.is_some()
-                        || current_properties.ry.is_some()
-                    {
-                        (
-                            current_properties.x.unwrap_or(0.0),
-                            current_properties.y.unwrap_or(0.0),
-                        )
-                    } else {
-                        (x, y)
-                    };
-
                     keys.push(Key {
                         legends,
                         properties: current_properties.clone(),
@@ -263,9 +250,6 @@ impl Keyboard {
                     current_properties.l = None;
                     current_properties.n = None;
                     current_properties.d = None;
-                    current_properties.r = None;
-                    current_properties.rx = None;
-                    current_properties.ry = None;
                 }
             }
             current_y += 1.0;
@@ -282,7 +266,8 @@ impl Keyboard {
             let mut parts = Vec::new();
 
             // For rotated keys, we want to preserve the exact order: r, rx, ry, y, x
-            if props.r.is_some() || props.rx.is_some() || props.ry.is_some() {
+            let rotated = props.r.is_some() || props.rx.is_some() || props.ry.is_some();
+            if rotated {
                 // This is a rotated key - use strict ordering
                 if props.r != last_props.r {
                     if let Some(r) = props.r {
@@ -301,7 +286,47 @@ impl Keyboard {
                         parts.push(format!("ry:{}", ry));
                     }
                 }
+            }
 
+            // Persistent properties only need to be written when they differ
+            // from the running state carried over from earlier keys.
+            if props.c != last_props.c {
+                if let Some(ref c) = props.c {
+                    parts.push(format!("c:{}", serde_json::to_string(c).unwrap()));
+                }
+            }
+            if props.t != last_props.t {
+                if let Some(ref t) = props.t {
+                    parts.push(format!("t:{}", serde_json::to_string(t).unwrap()));
+                }
+            }
+            if props.g != last_props.g {
+                if let Some(g) = props.g {
+                    parts.push(format!("g:{}", g));
+                }
+            }
+            if props.a != last_props.a {
+                if let Some(a) = props.a {
+                    parts.push(format!("a:{}", a));
+                }
+            }
+            if props.f != last_props.f {
+                if let Some(f) = props.f {
+                    parts.push(format!("f:{}", f));
+                }
+            }
+            if props.f2 != last_props.f2 {
+                if let Some(f2) = props.f2 {
+                    parts.push(format!("f2:{}", f2));
+                }
+            }
+            if props.p != last_props.p {
+                if let Some(ref p) = props.p {
+                    parts.push(format!("p:{}", serde_json::to_string(p).unwrap()));
+                }
+            }
+
+            if rotated {
                 // Always include y and x for rotated keys
                 if let Some(y) = props.y {
                     parts.push(format!("y:{}", y));
@@ -325,6 +350,37 @@ impl Keyboard {
                 }
             }
 
+            // Single-key properties are written whenever set on this key,
+            // since the parser resets them back to `None` once the key is
+            // emitted.
+            if let Some(w) = props.w {
+                parts.push(format!("w:{}", w));
+            }
+            if let Some(h) = props.h {
+                parts.push(format!("h:{}", h));
+            }
+            if let Some(x2) = props.x2 {
+                parts.push(format!("x2:{}", x2));
+            }
+            if let Some(y2) = props.y2 {
+                parts.push(format!("y2:{}", y2));
+            }
+            if let Some(w2) = props.w2 {
+                parts.push(format!("w2:{}", w2));
+            }
+            if let Some(h2) = props.h2 {
+                parts.push(format!("h2:{}", h2));
+            }
+            if let Some(l) = props.l {
+                parts.push(format!("l:{}", l));
+            }
+            if let Some(n) = props.n {
+                parts.push(format!("n:{}", n));
+            }
+            if let Some(d) = props.d {
+                parts.push(format!("d:{}", d));
+            }
+
             if parts.is_empty() {
                 None
             } else {
@@ -400,3 +456,55 @@ impl Keyboard {
         output
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn round_trips_colors_widths_and_secondary_legends() {
+        let raw = r##"
+            [{c:"#cc0000",w:1.5},"Tab","Q",{t:"#00cc00",a:7},"Caps\nLock",{c:"#0000cc",f:3,f2:5,w:2},"Enter"]
+        "##;
+
+        let keyboard = Keyboard::parse(raw).expect("initial parse should succeed");
+        let reserialized = keyboard.to_raw_format();
+        let roundtripped =
+            Keyboard::parse(&reserialized).expect("re-serialized output should parse");
+
+        assert_eq!(keyboard, roundtripped);
+    }
+
+    #[test]
+    fn rotation_persists_across_a_whole_cluster() {
+        let raw = r#"[{"r":15,"rx":4,"ry":1,"x":4,"y":1},"A","B"]"#;
+        let keyboard = Keyboard::parse(raw).expect("parse should succeed");
+
+        assert_eq!(keyboard.keys.len(), 2);
+
+        let a = &keyboard.keys[0];
+        assert_eq!((a.x, a.y), (4.0, 1.0));
+        assert_eq!(a.properties.r, Some(15.0));
+        assert_eq!(a.properties.rx, Some(4.0));
+        assert_eq!(a.properties.ry, Some(1.0));
+
+        // "B" has no property object of its own, so it must inherit the
+        // cluster's rotation instead of falling back to `r: None`.
+        let b = &keyboard.keys[1];
+        assert_eq!((b.x, b.y), (5.0, 0.0));
+        assert_eq!(b.properties.r, Some(15.0));
+        assert_eq!(b.properties.rx, Some(4.0));
+        assert_eq!(b.properties.ry, Some(1.0));
+    }
+
+    #[test]
+    fn round_trips_persistent_strings_with_control_characters() {
+        let raw = "[{\"p\":\"a\\u0001b\"},\"A\"]";
+        let keyboard = Keyboard::parse(raw).expect("initial parse should succeed");
+        let reserialized = keyboard.to_raw_format();
+        let roundtripped =
+            Keyboard::parse(&reserialized).expect("re-serialized output should parse");
+
+        assert_eq!(keyboard, roundtripped);
+    }
+}
@@ -0,0 +1,488 @@
+//! A small lexer/parser for KLE's relaxed, JSON5-ish textual syntax.
+//!
+//! KLE layout files are not quite JSON: object keys are often bare
+//! identifiers, both single- and double-quoted strings are accepted, trailing
+//! commas are common, and the whole document is a bare comma-separated list
+//! of rows rather than a single JSON value. This module scans that syntax
+//! into [`Token`]s with source positions and parses them directly into
+//! [`serde_json::Value`] trees, so a bad file reports the exact line/column
+//! of the offending token instead of an opaque `serde_json::Error`.
+
+use serde_json::Value;
+use std::fmt;
+
+/// A lexical token produced by [`Lexer`].
+#[derive(Debug, Clone, PartialEq)]
+pub enum Token {
+    LBracket,
+    RBracket,
+    LBrace,
+    RBrace,
+    Colon,
+    Comma,
+    String(String),
+    Number(f64),
+    Bool(bool),
+    Ident(String),
+    Eof,
+}
+
+impl fmt::Display for Token {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Token::LBracket => write!(f, "'['"),
+            Token::RBracket => write!(f, "']'"),
+            Token::LBrace => write!(f, "'{{'"),
+            Token::RBrace => write!(f, "'}}'"),
+            Token::Colon => write!(f, "':'"),
+            Token::Comma => write!(f, "','"),
+            Token::String(s) => write!(f, "string {:?}", s),
+            Token::Number(n) => write!(f, "number {}", n),
+            Token::Bool(b) => write!(f, "boolean {}", b),
+            Token::Ident(s) => write!(f, "identifier {:?}", s),
+            Token::Eof => write!(f, "end of input"),
+        }
+    }
+}
+
+/// A 1-based `(line, col)` position in the source.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Span {
+    pub line: usize,
+    pub col: usize,
+}
+
+#[derive(Debug, Clone)]
+struct SpannedToken {
+    token: Token,
+    span: Span,
+}
+
+/// An error produced while lexing or parsing KLE source, carrying the span of
+/// the offending token so callers can point at the exact character.
+#[derive(Debug, Clone)]
+pub struct ParseError {
+    pub message: String,
+    pub span: Span,
+}
+
+impl fmt::Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{} at line {}, column {}",
+            self.message, self.span.line, self.span.col
+        )
+    }
+}
+
+impl std::error::Error for ParseError {}
+
+/// Scans KLE's relaxed grammar into a stream of [`Token`]s, tracking
+/// `(line, col)` as it consumes characters.
+struct Lexer<'a> {
+    chars: std::iter::Peekable<std::str::Chars<'a>>,
+    line: usize,
+    col: usize,
+}
+
+impl<'a> Lexer<'a> {
+    fn new(source: &'a str) -> Self {
+        Lexer {
+            chars: source.chars().peekable(),
+            line: 1,
+            col: 1,
+        }
+    }
+
+    fn span(&self) -> Span {
+        Span {
+            line: self.line,
+            col: self.col,
+        }
+    }
+
+    fn bump(&mut self) -> Option<char> {
+        let c = self.chars.next()?;
+        if c == '\n' {
+            self.line += 1;
+            self.col = 1;
+        } else {
+            self.col += 1;
+        }
+        Some(c)
+    }
+
+    fn peek_char(&mut self) -> Option<char> {
+        self.chars.peek().copied()
+    }
+
+    fn skip_whitespace(&mut self) {
+        while let Some(c) = self.peek_char() {
+            if c.is_whitespace() {
+                self.bump();
+            } else {
+                break;
+            }
+        }
+    }
+
+    fn next_token(&mut self) -> Result<SpannedToken, ParseError> {
+        self.skip_whitespace();
+        let span = self.span();
+
+        let Some(c) = self.peek_char() else {
+            return Ok(SpannedToken {
+                token: Token::Eof,
+                span,
+            });
+        };
+
+        let token = match c {
+            '[' => {
+                self.bump();
+                Token::LBracket
+            }
+            ']' => {
+                self.bump();
+                Token::RBracket
+            }
+            '{' => {
+                self.bump();
+                Token::LBrace
+            }
+            '}' => {
+                self.bump();
+                Token::RBrace
+            }
+            ':' => {
+                self.bump();
+                Token::Colon
+            }
+            ',' => {
+                self.bump();
+                Token::Comma
+            }
+            '"' | '\'' => self.scan_string(c)?,
+            c if c == '-' || c.is_ascii_digit() => self.scan_number()?,
+            c if c.is_alphabetic() || c == '_' => self.scan_ident_or_keyword(),
+            other => {
+                return Err(ParseError {
+                    message: format!("unexpected character '{}'", other),
+                    span,
+                });
+            }
+        };
+
+        Ok(SpannedToken { token, span })
+    }
+
+    fn scan_string(&mut self, quote: char) -> Result<Token, ParseError> {
+        let start = self.span();
+        self.bump(); // opening quote
+        let mut value = String::new();
+        loop {
+            match self.bump() {
+                Some(c) if c == quote => break,
+                Some('\\') => match self.bump() {
+                    Some('"') => value.push('"'),
+                    Some('\'') => value.push('\''),
+                    Some('\\') => value.push('\\'),
+                    Some('/') => value.push('/'),
+                    Some('n') => value.push('\n'),
+                    Some('t') => value.push('\t'),
+                    Some('r') => value.push('\r'),
+                    Some('b') => value.push('\u{8}'),
+                    Some('f') => value.push('\u{c}'),
+                    Some('u') => {
+                        let mut code = 0u32;
+                        for _ in 0..4 {
+                            let d = self.bump().ok_or_else(|| ParseError {
+                                message: "unterminated unicode escape".to_string(),
+                                span: self.span(),
+                            })?;
+                            let digit = d.to_digit(16).ok_or_else(|| ParseError {
+                                message: format!("invalid unicode escape digit '{}'", d),
+                                span: self.span(),
+                            })?;
+                            code = code * 16 + digit;
+                        }
+                        value.push(char::from_u32(code).unwrap_or(char::REPLACEMENT_CHARACTER));
+                    }
+                    Some(other) => value.push(other),
+                    None => {
+                        return Err(ParseError {
+                            message: "unterminated string literal".to_string(),
+                            span: start,
+                        })
+                    }
+                },
+                Some(c) => value.push(c),
+                None => {
+                    return Err(ParseError {
+                        message: "unterminated string literal".to_string(),
+                        span: start,
+                    })
+                }
+            }
+        }
+        Ok(Token::String(value))
+    }
+
+    fn scan_number(&mut self) -> Result<Token, ParseError> {
+        let start = self.span();
+        let mut text = String::new();
+
+        if self.peek_char() == Some('-') {
+            text.push(self.bump().unwrap());
+        }
+        while let Some(c) = self.peek_char() {
+            if c.is_ascii_digit() {
+                text.push(c);
+                self.bump();
+            } else {
+                break;
+            }
+        }
+        if self.peek_char() == Some('.') {
+            text.push(self.bump().unwrap());
+            while let Some(c) = self.peek_char() {
+                if c.is_ascii_digit() {
+                    text.push(c);
+                    self.bump();
+                } else {
+                    break;
+                }
+            }
+        }
+        if matches!(self.peek_char(), Some('e') | Some('E')) {
+            text.push(self.bump().unwrap());
+            if matches!(self.peek_char(), Some('+') | Some('-')) {
+                text.push(self.bump().unwrap());
+            }
+            while let Some(c) = self.peek_char() {
+                if c.is_ascii_digit() {
+                    text.push(c);
+                    self.bump();
+                } else {
+                    break;
+                }
+            }
+        }
+
+        text.parse::<f64>().map(Token::Number).map_err(|_| ParseError {
+            message: format!("invalid number literal '{}'", text),
+            span: start,
+        })
+    }
+
+    fn scan_ident_or_keyword(&mut self) -> Token {
+        let mut text = String::new();
+        while let Some(c) = self.peek_char() {
+            if c.is_alphanumeric() || c == '_' {
+                text.push(c);
+                self.bump();
+            } else {
+                break;
+            }
+        }
+        match text.as_str() {
+            "true" => Token::Bool(true),
+            "false" => Token::Bool(false),
+            _ => Token::Ident(text),
+        }
+    }
+}
+
+/// A recursive-descent parser that turns a token stream into
+/// [`serde_json::Value`] nodes following KLE's relaxed grammar: bare
+/// identifier keys, single- or double-quoted strings, and trailing commas
+/// are all accepted.
+struct Parser<'a> {
+    lexer: Lexer<'a>,
+    current: SpannedToken,
+}
+
+impl<'a> Parser<'a> {
+    fn new(source: &'a str) -> Result<Self, ParseError> {
+        let mut lexer = Lexer::new(source);
+        let current = lexer.next_token()?;
+        Ok(Parser { lexer, current })
+    }
+
+    fn advance(&mut self) -> Result<(), ParseError> {
+        self.current = self.lexer.next_token()?;
+        Ok(())
+    }
+
+    fn expect(&mut self, expected: Token) -> Result<(), ParseError> {
+        if self.current.token == expected {
+            self.advance()
+        } else {
+            Err(ParseError {
+                message: format!(
+                    "expected {} but found {}",
+                    expected, self.current.token
+                ),
+                span: self.current.span,
+            })
+        }
+    }
+
+    /// A KLE document is a bare, comma-separated list of values (an optional
+    /// leading metadata object followed by row arrays) with no surrounding
+    /// brackets.
+    fn parse_document(&mut self) -> Result<Vec<Value>, ParseError> {
+        let mut values = Vec::new();
+        while self.current.token != Token::Eof {
+            values.push(self.parse_value()?);
+            if self.current.token == Token::Comma {
+                self.advance()?;
+            } else {
+                break;
+            }
+        }
+        if self.current.token != Token::Eof {
+            return Err(ParseError {
+                message: format!("unexpected trailing {}", self.current.token),
+                span: self.current.span,
+            });
+        }
+        Ok(values)
+    }
+
+    fn parse_value(&mut self) -> Result<Value, ParseError> {
+        match self.current.token.clone() {
+            Token::LBracket => self.parse_array(),
+            Token::LBrace => self.parse_object(),
+            Token::String(s) => {
+                self.advance()?;
+                Ok(Value::String(s))
+            }
+            Token::Number(n) => {
+                self.advance()?;
+                // Preserve integer-ness (e.g. `7` vs `7.5`) so fields typed as
+                // integers (like the alignment/font-size properties) deserialize
+                // correctly instead of always landing on a float representation.
+                let number = if n.fract() == 0.0 && n.abs() < i64::MAX as f64 {
+                    serde_json::Number::from(n as i64)
+                } else {
+                    match serde_json::Number::from_f64(n) {
+                        Some(number) => number,
+                        None => return Ok(Value::Null),
+                    }
+                };
+                Ok(Value::Number(number))
+            }
+            Token::Bool(b) => {
+                self.advance()?;
+                Ok(Value::Bool(b))
+            }
+            other => Err(ParseError {
+                message: format!("unexpected {} while expecting a value", other),
+                span: self.current.span,
+            }),
+        }
+    }
+
+    fn parse_array(&mut self) -> Result<Value, ParseError> {
+        self.expect(Token::LBracket)?;
+        let mut items = Vec::new();
+        while self.current.token != Token::RBracket {
+            items.push(self.parse_value()?);
+            if self.current.token == Token::Comma {
+                self.advance()?;
+            } else {
+                break;
+            }
+        }
+        self.expect(Token::RBracket)?;
+        Ok(Value::Array(items))
+    }
+
+    fn parse_object(&mut self) -> Result<Value, ParseError> {
+        self.expect(Token::LBrace)?;
+        let mut map = serde_json::Map::new();
+        while self.current.token != Token::RBrace {
+            let key = match self.current.token.clone() {
+                Token::String(s) => s,
+                Token::Ident(s) => s,
+                other => {
+                    return Err(ParseError {
+                        message: format!("expected an object key but found {}", other),
+                        span: self.current.span,
+                    })
+                }
+            };
+            self.advance()?;
+            self.expect(Token::Colon)?;
+            let value = self.parse_value()?;
+            map.insert(key, value);
+            if self.current.token == Token::Comma {
+                self.advance()?;
+            } else {
+                break;
+            }
+        }
+        self.expect(Token::RBrace)?;
+        Ok(Value::Object(map))
+    }
+}
+
+/// Parses a raw KLE document into its top-level values (the optional leading
+/// metadata object followed by one array per row), reporting the line/column
+/// of the first syntax error encountered.
+pub fn parse_document(source: &str) -> Result<Vec<Value>, ParseError> {
+    Parser::new(source)?.parse_document()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_nested_objects() {
+        let values = parse_document(
+            r##"{name:"My Board",background:{name:"dark",style:"#000000"}}"##,
+        )
+        .expect("nested object should parse");
+
+        assert_eq!(values.len(), 1);
+        assert_eq!(values[0]["name"], Value::String("My Board".to_string()));
+        assert_eq!(
+            values[0]["background"]["style"],
+            Value::String("#000000".to_string())
+        );
+    }
+
+    #[test]
+    fn parses_escaped_quotes_in_legends() {
+        let values = parse_document(r#"["He said \"hi\"","Q"]"#).expect("should parse");
+
+        assert_eq!(
+            values[0],
+            Value::Array(vec![
+                Value::String("He said \"hi\"".to_string()),
+                Value::String("Q".to_string()),
+            ])
+        );
+    }
+
+    #[test]
+    fn parses_scientific_notation_numbers() {
+        let values = parse_document(r#"[{x:1e3,y:-1.5e-2}]"#).expect("should parse");
+
+        let props = &values[0][0];
+        assert_eq!(props["x"].as_f64(), Some(1000.0));
+        assert_eq!(props["y"].as_f64(), Some(-0.015));
+    }
+
+    #[test]
+    fn reports_the_line_and_column_of_a_syntax_error() {
+        let err = parse_document("[\n  \"A\",\n  @\n]").unwrap_err();
+
+        assert_eq!(err.span.line, 3);
+        assert_eq!(err.span.col, 3);
+        assert!(err.message.contains('@'));
+    }
+}
@@ -0,0 +1,67 @@
+//! A legend search index over a parsed [`Keyboard`], so tooling can look up
+//! keys by their printed text without re-scanning `keys` on every call.
+
+use std::collections::HashMap;
+
+use crate::{Key, Keyboard};
+
+/// An index from normalized legend text to the keys that carry it, built
+/// once over a [`Keyboard`]'s keys and queried as many times as needed.
+///
+/// Legends are normalized by lowercasing and trimming each `\n`-separated
+/// line independently, so a query ignores case and which position on the
+/// key the matching line is in. This also lays the groundwork for fuzzy
+/// matching: `index` is keyed by the same normalized tokens a future
+/// approximate-match pass would score against.
+pub struct LegendIndex<'a> {
+    keys: &'a [Key],
+    index: HashMap<String, Vec<usize>>,
+}
+
+impl<'a> LegendIndex<'a> {
+    /// Walks `keys`, normalizing every legend line (lowercase, trim), and
+    /// maps each normalized token to the indices of the keys that carry it.
+    pub fn build(keys: &'a [Key]) -> Self {
+        let mut index: HashMap<String, Vec<usize>> = HashMap::new();
+        for (i, key) in keys.iter().enumerate() {
+            for legend in &key.legends {
+                let token = normalize(legend);
+                if token.is_empty() {
+                    continue;
+                }
+                index.entry(token).or_default().push(i);
+            }
+        }
+        LegendIndex { keys, index }
+    }
+
+    /// Returns every key with a legend line that exactly matches `text`
+    /// once both are normalized (lowercased and trimmed).
+    pub fn find(&self, text: &str) -> Vec<&'a Key> {
+        match self.index.get(&normalize(text)) {
+            Some(indices) => indices.iter().map(|&i| &self.keys[i]).collect(),
+            None => Vec::new(),
+        }
+    }
+}
+
+fn normalize(legend: &str) -> String {
+    legend.trim().to_lowercase()
+}
+
+impl Keyboard {
+    /// Builds a [`LegendIndex`] over this layout's keys.
+    pub fn legend_index(&self) -> LegendIndex<'_> {
+        LegendIndex::build(&self.keys)
+    }
+
+    /// Returns every key with a legend line matching `text`, case-insensitive
+    /// and ignoring which `\n`-separated position on the key it's in.
+    ///
+    /// This builds a fresh [`LegendIndex`] for the call; callers doing many
+    /// lookups over the same layout should build one with
+    /// [`Keyboard::legend_index`] instead and reuse it.
+    pub fn find_legend(&self, text: &str) -> Vec<&Key> {
+        self.legend_index().find(text)
+    }
+}
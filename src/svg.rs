@@ -0,0 +1,182 @@
+//! Renders a parsed [`Keyboard`] as an SVG document, so layouts can be
+//! previewed without a full KLE-compatible UI.
+
+use crate::Keyboard;
+
+/// Pixels per KLE unit (1u) when no explicit unit size is given.
+const DEFAULT_UNIT_SIZE: f64 = 54.0;
+
+const DEFAULT_KEY_COLOR: &str = "#cccccc";
+const DEFAULT_TEXT_COLOR: &str = "#000000";
+const DEFAULT_BACKGROUND_COLOR: &str = "#eeeeee";
+const KEY_CORNER_RADIUS: f64 = 5.0;
+const KEY_STROKE_COLOR: &str = "#999999";
+
+/// Anchor fractions (of key width/height) and SVG `text-anchor` value for
+/// each of KLE's `a` alignment indices. The crate only keeps legends as a
+/// flat, newline-split list rather than the full 12-slot KLE grid, so this
+/// approximates the eight most common anchor points rather than every
+/// legend position real KLE supports.
+const LEGEND_ANCHORS: [(f64, f64, &str); 8] = [
+    (0.08, 0.3, "start"),  // 0: top-left
+    (0.08, 0.95, "start"), // 1: bottom-left
+    (0.92, 0.3, "end"),    // 2: top-right
+    (0.5, 0.3, "middle"),  // 3: top-center
+    (0.5, 0.6, "middle"),  // 4: center
+    (0.92, 0.95, "end"),   // 5: bottom-right
+    (0.5, 0.95, "middle"), // 6: bottom-center
+    (0.08, 0.6, "start"),  // 7: center-left
+];
+
+fn escape_xml(s: &str) -> String {
+    s.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+/// Maps a KLE font-size index (roughly `1..=9`) to a pixel size.
+fn font_size_px(size: Option<u8>) -> f64 {
+    size.unwrap_or(3) as f64 * 4.0 + 4.0
+}
+
+impl Keyboard {
+    /// Renders the layout as an SVG document using the default unit size
+    /// (the on-screen pixel size of a 1u key).
+    pub fn to_svg(&self) -> String {
+        self.to_svg_with_unit_size(DEFAULT_UNIT_SIZE)
+    }
+
+    /// Renders the layout as an SVG document, scaling every key by
+    /// `unit_size` pixels per KLE unit.
+    pub fn to_svg_with_unit_size(&self, unit_size: f64) -> String {
+        let background_color = self
+            .metadata
+            .as_ref()
+            .and_then(|m| m.backcolor.clone())
+            .or_else(|| {
+                self.metadata.as_ref().and_then(|m| {
+                    m.background
+                        .as_ref()
+                        .filter(|b| b.style.starts_with('#'))
+                        .map(|b| b.style.clone())
+                })
+            })
+            .unwrap_or_else(|| DEFAULT_BACKGROUND_COLOR.to_string());
+        let background_color = escape_xml(&background_color);
+
+        let mut max_x: f64 = 0.0;
+        let mut max_y: f64 = 0.0;
+        for key in &self.keys {
+            let w = key.properties.w.unwrap_or(1.0);
+            let h = key.properties.h.unwrap_or(1.0);
+            max_x = max_x.max(key.x + w);
+            max_y = max_y.max(key.y + h);
+        }
+
+        let width_px = max_x * unit_size;
+        let height_px = max_y * unit_size;
+
+        let mut svg = String::new();
+        svg.push_str(&format!(
+            "<svg xmlns=\"http://www.w3.org/2000/svg\" width=\"{width_px}\" height=\"{height_px}\" viewBox=\"0 0 {width_px} {height_px}\">\n"
+        ));
+        svg.push_str(&format!(
+            "  <rect x=\"0\" y=\"0\" width=\"{width_px}\" height=\"{height_px}\" fill=\"{background_color}\"/>\n"
+        ));
+
+        for key in &self.keys {
+            svg.push_str(&self.render_key(key, unit_size));
+        }
+
+        svg.push_str("</svg>\n");
+        svg
+    }
+
+    fn render_key(&self, key: &crate::Key, unit_size: f64) -> String {
+        let props = &key.properties;
+        let w_px = props.w.unwrap_or(1.0) * unit_size;
+        let h_px = props.h.unwrap_or(1.0) * unit_size;
+        let x_px = key.x * unit_size;
+        let y_px = key.y * unit_size;
+
+        let key_color = escape_xml(
+            &props
+                .c
+                .clone()
+                .unwrap_or_else(|| DEFAULT_KEY_COLOR.to_string()),
+        );
+        let text_color = escape_xml(
+            &props
+                .t
+                .clone()
+                .unwrap_or_else(|| DEFAULT_TEXT_COLOR.to_string()),
+        );
+
+        let mut group = String::new();
+        let transform = match (props.r, props.rx, props.ry) {
+            (None, None, None) => String::new(),
+            (r, rx, ry) => {
+                let angle = r.unwrap_or(0.0);
+                let center_x = rx.unwrap_or(0.0) * unit_size;
+                let center_y = ry.unwrap_or(0.0) * unit_size;
+                format!(" transform=\"rotate({angle} {center_x} {center_y})\"")
+            }
+        };
+
+        group.push_str(&format!("  <g{transform}>\n"));
+        group.push_str(&format!(
+            "    <rect x=\"{x_px}\" y=\"{y_px}\" width=\"{w_px}\" height=\"{h_px}\" rx=\"{KEY_CORNER_RADIUS}\" fill=\"{key_color}\" stroke=\"{KEY_STROKE_COLOR}\"/>\n"
+        ));
+
+        let (anchor_x, anchor_y, text_anchor) =
+            LEGEND_ANCHORS[props.a.unwrap_or(0) as usize % LEGEND_ANCHORS.len()];
+        let font_px = font_size_px(props.f);
+        for (i, legend) in key.legends.iter().enumerate() {
+            if legend.is_empty() {
+                continue;
+            }
+            let size = if i == 0 { font_px } else { font_size_px(props.f2) };
+            let text_x = x_px + anchor_x * w_px;
+            let text_y = y_px + anchor_y * h_px + i as f64 * size;
+            group.push_str(&format!(
+                "    <text x=\"{text_x}\" y=\"{text_y}\" font-size=\"{size}\" text-anchor=\"{text_anchor}\" fill=\"{text_color}\">{}</text>\n",
+                escape_xml(legend)
+            ));
+        }
+
+        group.push_str("  </g>\n");
+        group
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::Keyboard;
+
+    #[test]
+    fn every_key_in_a_rotated_cluster_gets_a_rotate_transform() {
+        let raw = r#"[{"r":15,"rx":4,"ry":1,"x":4,"y":1},"A","B"]"#;
+        let keyboard = Keyboard::parse(raw).expect("parse should succeed");
+        let svg = keyboard.to_svg();
+
+        assert_eq!(
+            svg.matches("transform=\"rotate(15").count(),
+            2,
+            "every key in the cluster should inherit the rotation, not just the first:\n{svg}"
+        );
+    }
+
+    #[test]
+    fn escapes_color_fields_in_svg_attributes() {
+        let raw = r#"[{"c":"red\" onload=\"alert(1)\" x=\""},"A"]"#;
+        let keyboard = Keyboard::parse(raw).expect("parse should succeed");
+        let svg = keyboard.to_svg();
+
+        assert!(
+            !svg.contains("\" onload=\""),
+            "unescaped attribute injection breaks out of the fill attribute:\n{svg}"
+        );
+        assert!(svg.contains("&quot;"), "expected the quote to be escaped:\n{svg}");
+    }
+}